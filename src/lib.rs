@@ -1,67 +1,754 @@
-use indicatif::{ParallelProgressIterator, ProgressBar, ProgressStyle};
+// pyo3's #[pyfunction] expansion triggers a useless_conversion false positive on every
+// PyResult-returning wrapper it generates; silence it crate-wide rather than per-function.
+#![allow(clippy::useless_conversion)]
+
+use indicatif::{
+    MultiProgress, ParallelProgressIterator, ProgressBar, ProgressDrawTarget, ProgressStyle,
+};
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
 use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// Redraw the progress bar at most this often, so extremely fast inner iterations don't
+/// bottleneck on terminal writes.
+const PROGRESS_REFRESH_HZ: u8 = 40;
+
+const FANCY_TEMPLATE: &str =
+    "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})";
+const PLAIN_TEMPLATE: &str = "{pos}/{len} pairs done ({percent}%), elapsed {elapsed_precise}";
+
+/// Coefficients of the single-shot randomized-measurement pair estimator
+/// `rho_hat = (d+1)|b><b| - I` for local (qudit) dimension `d`, derived once per call instead
+/// of being hard-wired to the `d = 2` (qubit) special case.
+struct QuditCoefficients {
+    /// Same basis, same outcome: `d^2 + d - 1` (5.0 at `d = 2`).
+    same_outcome: f64,
+    /// Same basis, different outcome: `-(d + 2)` (-4.0 at `d = 2`).
+    same_basis_diff_outcome: f64,
+    /// Different basis: the averaged overlap `1/d` (0.5 at `d = 2`).
+    diff_basis: f64,
+}
+
+impl QuditCoefficients {
+    fn for_local_dim(local_dim: u32) -> PyResult<Self> {
+        if local_dim < 2 {
+            return Err(PyValueError::new_err(format!(
+                "local_dim must be >= 2, got {local_dim}"
+            )));
+        }
+        let d = local_dim as f64;
+        Ok(Self {
+            same_outcome: d * d + d - 1.0,
+            same_basis_diff_outcome: -(d + 2.0),
+            diff_basis: 1.0 / d,
+        })
+    }
+}
 
 fn rho_elt_process(
-    rho_a_i: &String,
-    rho_b_i: &String,
-    rho_a_i1: &String,
-    rho_b_i1: &String,
+    rho_a_i: u8,
+    rho_b_i: u8,
+    rho_a_i1: u8,
+    rho_b_i1: u8,
+    coeffs: &QuditCoefficients,
 ) -> f64 {
     if rho_a_i != rho_b_i {
-        0.5
+        coeffs.diff_basis
     } else {
         if rho_a_i1 == rho_b_i1 {
-            5.0
+            coeffs.same_outcome
         } else {
-            -4.0
+            coeffs.same_basis_diff_outcome
         }
     }
 }
 
-fn get_trace(rho_a: &Vec<String>, rho_b: &Vec<String>, substring_index: &Vec<usize>) -> f64 {
+fn get_trace(
+    rho_a: &[u8],
+    rho_b: &[u8],
+    substring_index: &[usize],
+    coeffs: &QuditCoefficients,
+) -> f64 {
     if substring_index.is_empty() {
         return 1.0;
     }
 
     substring_index.iter().fold(1.0, |acc, &i| {
-        acc * rho_elt_process(&rho_a[i], &rho_b[i], &rho_a[i + 1], &rho_b[i + 1])
+        acc * rho_elt_process(rho_a[i], rho_b[i], rho_a[i + 1], rho_b[i + 1], coeffs)
     })
 }
 
-#[pyfunction]
-fn perform_trace_calculation(data: Vec<Vec<String>>, subs: Vec<usize>) -> f64 {
-    let num_samples = data.len();
-    let substring_indices: Vec<usize> = subs.iter().map(|&i| i * 2).collect();
+/// Interns the distinct measurement symbols in `data` to small integers once, so the hot
+/// trace kernel compares `u8`s instead of heap-allocated `String`s billions of times. Errors
+/// if `data` contains more than `u8::MAX + 1` distinct symbols, since a wider encoding would
+/// be needed to assign them all unique ids.
+fn intern_symbols(data: &[Vec<String>]) -> PyResult<Vec<Vec<u8>>> {
+    let mut symbol_ids: HashMap<&str, u8> = HashMap::new();
+    data.iter()
+        .map(|row| {
+            row.iter()
+                .map(|symbol| {
+                    if let Some(&id) = symbol_ids.get(symbol.as_str()) {
+                        return Ok(id);
+                    }
+                    let next_id = symbol_ids.len();
+                    if next_id > u8::MAX as usize {
+                        return Err(PyValueError::new_err(format!(
+                            "more than {} distinct measurement symbols, which can't be encoded as u8",
+                            u8::MAX as usize + 1
+                        )));
+                    }
+                    symbol_ids.insert(symbol.as_str(), next_id as u8);
+                    Ok(next_id as u8)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Number of unordered pairs `(i, j), i < j` among `n` samples.
+fn num_pairs(n: usize) -> u64 {
+    let n = n as u64;
+    n * n.saturating_sub(1) / 2
+}
 
-    let bar = ProgressBar::new(num_samples as u64);
-    bar.set_style(
-        ProgressStyle::default_bar()
-            .template(
-                "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})",
+/// Number of unordered pairs `(i, j), i < j` with `i` restricted to rows `[0, x)` among `n`
+/// samples: `sum_{i=0}^{x-1} (n-1-i)`.
+fn pairs_before(x: usize, n: usize) -> u64 {
+    let x = x as u64;
+    let n = n as u64;
+    x * (2 * n - x - 1) / 2
+}
+
+/// A `MultiProgress` with one summary bar for the overall pair count and one child bar per
+/// contiguous row chunk, so the load imbalance across the uneven triangular rows (and hence
+/// across Rayon workers) is visible instead of hidden behind a single aggregate bar.
+struct MultiProgressView {
+    multi: MultiProgress,
+    summary: ProgressBar,
+    chunk_of_row: Vec<usize>,
+    chunk_bars: Vec<ProgressBar>,
+}
+
+impl MultiProgressView {
+    fn new(num_samples: usize, num_chunks: usize) -> Self {
+        let multi =
+            MultiProgress::with_draw_target(ProgressDrawTarget::stdout_with_hz(PROGRESS_REFRESH_HZ));
+
+        let summary = multi.add(ProgressBar::new(num_pairs(num_samples)));
+        summary.set_style(
+            ProgressStyle::with_template(
+                "total  {spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})",
             )
             .unwrap()
             .progress_chars("#>-"),
-    );
+        );
 
-    let result = (0..num_samples)
+        let num_chunks = num_chunks.clamp(1, num_samples.max(1));
+        let chunk_len = num_samples.div_ceil(num_chunks);
+
+        let mut chunk_of_row = vec![0usize; num_samples];
+        let mut chunk_bars = Vec::with_capacity(num_chunks);
+        let mut start = 0;
+        while start < num_samples {
+            let end = (start + chunk_len).min(num_samples);
+            let chunk_id = chunk_bars.len();
+
+            let bar = multi.add(ProgressBar::new(
+                pairs_before(end, num_samples) - pairs_before(start, num_samples),
+            ));
+            bar.set_style(
+                ProgressStyle::with_template(&format!(
+                    "worker {chunk_id:>2} [{{bar:30.cyan/blue}}] {{pos}}/{{len}}"
+                ))
+                .unwrap()
+                .progress_chars("#>-"),
+            );
+
+            chunk_of_row[start..end].fill(chunk_id);
+            chunk_bars.push(bar);
+            start = end;
+        }
+
+        Self {
+            multi,
+            summary,
+            chunk_of_row,
+            chunk_bars,
+        }
+    }
+
+    fn record_pair(&self, row: usize) {
+        self.chunk_bars[self.chunk_of_row[row]].inc(1);
+        self.summary.inc(1);
+    }
+
+    /// Tears down every child bar and the summary bar, leaving the terminal clean whether the
+    /// computation ran to completion or the Python call was interrupted.
+    fn finish(&self) {
+        for bar in &self.chunk_bars {
+            bar.finish_and_clear();
+        }
+        self.summary.finish_and_clear();
+        self.multi.clear().ok();
+    }
+}
+
+impl Drop for MultiProgressView {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Runs `f` inside a local Rayon thread pool capped at `num_threads`, or on the global pool if
+/// `None`, without touching the global pool's configuration.
+fn with_optional_pool<F, R>(num_threads: Option<usize>, f: F) -> PyResult<R>
+where
+    F: FnOnce() -> R + Send,
+    R: Send,
+{
+    match num_threads {
+        Some(n) => {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(n)
+                .build()
+                .map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+            Ok(pool.install(f))
+        }
+        None => Ok(f()),
+    }
+}
+
+/// Builds the progress bar used to report pair-completion during the hot loop. `mode` is one
+/// of `"hidden"` (no output, for notebooks/batch pipelines), `"plain"` (a single redrawn
+/// uncoloured line) or `"fancy"` (the styled interactive bar). `"plain"` and `"fancy"` both
+/// draw to stdout only when it is a terminal; with redirected stdout they print nothing.
+/// `template` overrides the default `indicatif` template for `"plain"`/`"fancy"` modes.
+fn build_progress_bar(total_pairs: u64, mode: &str, template: Option<&str>) -> PyResult<ProgressBar> {
+    if mode == "hidden" {
+        return Ok(ProgressBar::hidden());
+    }
+
+    let default_template = match mode {
+        "plain" => PLAIN_TEMPLATE,
+        "fancy" => FANCY_TEMPLATE,
+        other => {
+            return Err(PyValueError::new_err(format!(
+                "unknown progress_mode '{other}', expected 'hidden', 'plain', or 'fancy'"
+            )))
+        }
+    };
+
+    let style = ProgressStyle::with_template(template.unwrap_or(default_template))
+        .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let style = if mode == "fancy" {
+        style.progress_chars("#>-")
+    } else {
+        style
+    };
+
+    let bar = ProgressBar::new(total_pairs);
+    bar.set_style(style);
+    bar.set_draw_target(ProgressDrawTarget::stdout_with_hz(PROGRESS_REFRESH_HZ));
+    Ok(bar)
+}
+
+/// Sums `get_trace` over every unordered pair `(i, j), i < j` in `data` as a single flat
+/// parallel reduction, rather than nesting a `par_iter` row loop inside another. The progress
+/// bar is driven off pairs completed, not the outer row index, so it reflects the true
+/// (triangular, unbalanced-per-row) workload.
+fn sum_pairs(
+    data: &[Vec<u8>],
+    substring_indices: &[usize],
+    coeffs: &QuditCoefficients,
+    progress_mode: &str,
+    progress_template: Option<&str>,
+) -> PyResult<f64> {
+    let num_samples = data.len();
+    let bar = build_progress_bar(num_pairs(num_samples), progress_mode, progress_template)?;
+
+    Ok((0..num_samples)
         .into_par_iter()
+        .flat_map(|i| {
+            (i + 1..num_samples)
+                .into_par_iter()
+                .map(move |j| get_trace(&data[i], &data[j], substring_indices, coeffs))
+        })
         .progress_with(bar)
-        .map(|i| {
-            let row_sum: f64 = (i + 1..num_samples)
+        .sum())
+}
+
+/// Same pair sum as [`sum_pairs`], but reports progress through a [`MultiProgressView`]
+/// instead of a single bar, splitting the rows into at most `num_chunks` contiguous groups so callers
+/// can see how unevenly the triangular workload is spread across workers.
+fn sum_pairs_with_multi_progress(
+    data: &[Vec<u8>],
+    substring_indices: &[usize],
+    coeffs: &QuditCoefficients,
+    num_chunks: usize,
+) -> f64 {
+    let num_samples = data.len();
+    let view = MultiProgressView::new(num_samples, num_chunks);
+
+    let result = (0..num_samples)
+        .into_par_iter()
+        .flat_map(|i| {
+            (i + 1..num_samples)
                 .into_par_iter()
-                .map(|j| get_trace(&data[i], &data[j], &substring_indices))
-                .sum();
-            row_sum
+                .map(move |j| (i, get_trace(&data[i], &data[j], substring_indices, coeffs)))
+        })
+        .inspect(|(i, _)| view.record_pair(*i))
+        .map(|(_, val)| val)
+        .sum();
+
+    view.finish();
+    result
+}
+
+fn run_trace_calculation(
+    data: Vec<Vec<u8>>,
+    subs: Vec<usize>,
+    local_dim: u32,
+    num_threads: Option<usize>,
+    progress_mode: String,
+    progress_template: Option<String>,
+) -> PyResult<f64> {
+    let substring_indices: Vec<usize> = subs.iter().map(|&i| i * 2).collect();
+    let coeffs = QuditCoefficients::for_local_dim(local_dim)?;
+
+    if progress_mode == "multi" {
+        let num_chunks = num_threads.unwrap_or_else(rayon::current_num_threads);
+        return with_optional_pool(num_threads, || {
+            sum_pairs_with_multi_progress(&data, &substring_indices, &coeffs, num_chunks)
+        });
+    }
+
+    with_optional_pool(num_threads, || {
+        sum_pairs(
+            &data,
+            &substring_indices,
+            &coeffs,
+            &progress_mode,
+            progress_template.as_deref(),
+        )
+    })?
+}
+
+/// Thin wrapper over [`perform_trace_calculation_u8`] that interns the string symbols to
+/// `u8` once up front, so Python callers that already have string-labelled snapshots don't
+/// pay for the encoding themselves. `progress_mode` is `"hidden"`, `"plain"`, `"fancy"` (see
+/// [`build_progress_bar`]), or `"multi"` for a per-row-chunk [`MultiProgress`] view.
+#[pyfunction]
+#[pyo3(signature = (data, subs, local_dim=2, num_threads=None, progress_mode="fancy".to_string(), progress_template=None))]
+fn perform_trace_calculation(
+    data: Vec<Vec<String>>,
+    subs: Vec<usize>,
+    local_dim: u32,
+    num_threads: Option<usize>,
+    progress_mode: String,
+    progress_template: Option<String>,
+) -> PyResult<f64> {
+    run_trace_calculation(
+        intern_symbols(&data)?,
+        subs,
+        local_dim,
+        num_threads,
+        progress_mode,
+        progress_template,
+    )
+}
+
+/// Same as [`perform_trace_calculation`], but takes snapshots pre-encoded as small integers
+/// (one per measurement basis/outcome symbol) to avoid `String` comparison overhead in the
+/// hot pair loop.
+#[pyfunction]
+#[pyo3(signature = (data, subs, local_dim=2, num_threads=None, progress_mode="fancy".to_string(), progress_template=None))]
+fn perform_trace_calculation_u8(
+    data: Vec<Vec<u8>>,
+    subs: Vec<usize>,
+    local_dim: u32,
+    num_threads: Option<usize>,
+    progress_mode: String,
+    progress_template: Option<String>,
+) -> PyResult<f64> {
+    run_trace_calculation(data, subs, local_dim, num_threads, progress_mode, progress_template)
+}
+
+/// Sums `get_trace` over every pair while also accumulating each sample's row sum
+/// `R_k = sum_{j != k} get_trace(s_k, s_j)`, which the jackknife variance estimate needs.
+/// Each unordered pair `(i, j)` contributes its value to both `R_i` and `R_j`.
+fn sum_pairs_with_row_sums(
+    data: &[Vec<u8>],
+    substring_indices: &[usize],
+    coeffs: &QuditCoefficients,
+    progress_mode: &str,
+    progress_template: Option<&str>,
+) -> PyResult<Vec<f64>> {
+    let num_samples = data.len();
+    let bar = build_progress_bar(num_pairs(num_samples), progress_mode, progress_template)?;
+
+    Ok((0..num_samples)
+        .into_par_iter()
+        .flat_map(|i| {
+            (i + 1..num_samples).into_par_iter().map(move |j| {
+                let val = get_trace(&data[i], &data[j], substring_indices, coeffs);
+                (i, j, val)
+            })
+        })
+        .progress_with(bar)
+        .fold(
+            || vec![0.0; num_samples],
+            |mut row_sums, (i, j, val)| {
+                row_sums[i] += val;
+                row_sums[j] += val;
+                row_sums
+            },
+        )
+        .reduce(
+            || vec![0.0; num_samples],
+            |mut a, b| {
+                for (x, y) in a.iter_mut().zip(b) {
+                    *x += y;
+                }
+                a
+            },
+        ))
+}
+
+/// Same row-sum accumulation as [`sum_pairs_with_row_sums`], but reports progress through a
+/// [`MultiProgressView`] instead of a single bar.
+fn sum_pairs_with_row_sums_multi_progress(
+    data: &[Vec<u8>],
+    substring_indices: &[usize],
+    coeffs: &QuditCoefficients,
+    num_chunks: usize,
+) -> Vec<f64> {
+    let num_samples = data.len();
+    let view = MultiProgressView::new(num_samples, num_chunks);
+
+    let row_sums = (0..num_samples)
+        .into_par_iter()
+        .flat_map(|i| {
+            (i + 1..num_samples).into_par_iter().map(move |j| {
+                let val = get_trace(&data[i], &data[j], substring_indices, coeffs);
+                (i, j, val)
+            })
         })
+        .inspect(|(i, _, _)| view.record_pair(*i))
+        .fold(
+            || vec![0.0; num_samples],
+            |mut row_sums, (i, j, val)| {
+                row_sums[i] += val;
+                row_sums[j] += val;
+                row_sums
+            },
+        )
+        .reduce(
+            || vec![0.0; num_samples],
+            |mut a, b| {
+                for (x, y) in a.iter_mut().zip(b) {
+                    *x += y;
+                }
+                a
+            },
+        );
+
+    view.finish();
+    row_sums
+}
+
+/// Turns per-sample row sums into a jackknife estimate and standard error for the pairwise
+/// U-statistic `theta = 2T / (M(M-1))`, where `T` is the upper-triangular sum.
+fn jackknife_from_row_sums(row_sums: &[f64]) -> (f64, f64) {
+    let m = row_sums.len() as f64;
+    let total: f64 = row_sums.iter().sum::<f64>() / 2.0;
+    let estimate = 2.0 * total / (m * (m - 1.0));
+
+    let leave_one_out: Vec<f64> = row_sums
+        .iter()
+        .map(|&r_k| 2.0 * (total - r_k) / ((m - 1.0) * (m - 2.0)))
+        .collect();
+    let mean_loo = leave_one_out.iter().sum::<f64>() / m;
+    let variance: f64 = leave_one_out
+        .iter()
+        .map(|&theta_k| (theta_k - mean_loo).powi(2))
         .sum();
+    let std_error = ((m - 1.0) / m * variance).sqrt();
+
+    (estimate, std_error)
+}
+
+fn run_trace_calculation_with_error(
+    data: Vec<Vec<u8>>,
+    subs: Vec<usize>,
+    local_dim: u32,
+    num_threads: Option<usize>,
+    progress_mode: String,
+    progress_template: Option<String>,
+) -> PyResult<(f64, f64, Vec<f64>)> {
+    if data.len() < 3 {
+        return Err(PyValueError::new_err(format!(
+            "jackknife error estimation needs at least 3 samples, got {}",
+            data.len()
+        )));
+    }
+
+    let substring_indices: Vec<usize> = subs.iter().map(|&i| i * 2).collect();
+    let coeffs = QuditCoefficients::for_local_dim(local_dim)?;
+
+    let row_sums = if progress_mode == "multi" {
+        let num_chunks = num_threads.unwrap_or_else(rayon::current_num_threads);
+        with_optional_pool(num_threads, || {
+            sum_pairs_with_row_sums_multi_progress(&data, &substring_indices, &coeffs, num_chunks)
+        })?
+    } else {
+        with_optional_pool(num_threads, || {
+            sum_pairs_with_row_sums(
+                &data,
+                &substring_indices,
+                &coeffs,
+                &progress_mode,
+                progress_template.as_deref(),
+            )
+        })??
+    };
+
+    let (estimate, std_error) = jackknife_from_row_sums(&row_sums);
+    Ok((estimate, std_error, row_sums))
+}
+
+/// Like [`perform_trace_calculation`], but also returns a jackknife standard error and the
+/// per-sample row sums it was computed from, as `(estimate, std_error, per_sample_row_sums)`.
+#[pyfunction]
+#[pyo3(signature = (data, subs, local_dim=2, num_threads=None, progress_mode="fancy".to_string(), progress_template=None))]
+fn perform_trace_calculation_with_error(
+    data: Vec<Vec<String>>,
+    subs: Vec<usize>,
+    local_dim: u32,
+    num_threads: Option<usize>,
+    progress_mode: String,
+    progress_template: Option<String>,
+) -> PyResult<(f64, f64, Vec<f64>)> {
+    run_trace_calculation_with_error(
+        intern_symbols(&data)?,
+        subs,
+        local_dim,
+        num_threads,
+        progress_mode,
+        progress_template,
+    )
+}
 
+/// Same as [`perform_trace_calculation_with_error`], but takes pre-encoded `u8` snapshots.
+#[pyfunction]
+#[pyo3(signature = (data, subs, local_dim=2, num_threads=None, progress_mode="fancy".to_string(), progress_template=None))]
+fn perform_trace_calculation_with_error_u8(
+    data: Vec<Vec<u8>>,
+    subs: Vec<usize>,
+    local_dim: u32,
+    num_threads: Option<usize>,
+    progress_mode: String,
+    progress_template: Option<String>,
+) -> PyResult<(f64, f64, Vec<f64>)> {
+    run_trace_calculation_with_error(
+        data,
+        subs,
+        local_dim,
+        num_threads,
+        progress_mode,
+        progress_template,
+    )
+}
+
+/// Walks the pair space once and accumulates a `get_trace` sum per region in `subs_list`,
+/// amortizing the O(N^2) enumeration across all requested subsystems instead of repeating
+/// it once per call to `perform_trace_calculation`.
+fn sum_pairs_batch(
+    data: &[Vec<u8>],
+    substring_indices_list: &[Vec<usize>],
+    coeffs: &QuditCoefficients,
+    progress_mode: &str,
+    progress_template: Option<&str>,
+) -> PyResult<Vec<f64>> {
+    let num_samples = data.len();
+    let num_regions = substring_indices_list.len();
+    let bar = build_progress_bar(num_pairs(num_samples), progress_mode, progress_template)?;
+
+    Ok((0..num_samples)
+        .into_par_iter()
+        .flat_map(|i| {
+            (i + 1..num_samples).into_par_iter().map(move |j| {
+                substring_indices_list
+                    .iter()
+                    .map(|indices| get_trace(&data[i], &data[j], indices, coeffs))
+                    .collect::<Vec<f64>>()
+            })
+        })
+        .progress_with(bar)
+        .reduce(
+            || vec![0.0; num_regions],
+            |mut acc, pair_sums| {
+                for (a, p) in acc.iter_mut().zip(pair_sums) {
+                    *a += p;
+                }
+                acc
+            },
+        ))
+}
+
+/// Same per-region batch sum as [`sum_pairs_batch`], but reports progress through a
+/// [`MultiProgressView`] instead of a single bar.
+fn sum_pairs_batch_with_multi_progress(
+    data: &[Vec<u8>],
+    substring_indices_list: &[Vec<usize>],
+    coeffs: &QuditCoefficients,
+    num_chunks: usize,
+) -> Vec<f64> {
+    let num_samples = data.len();
+    let num_regions = substring_indices_list.len();
+    let view = MultiProgressView::new(num_samples, num_chunks);
+
+    let result = (0..num_samples)
+        .into_par_iter()
+        .flat_map(|i| {
+            (i + 1..num_samples).into_par_iter().map(move |j| {
+                let pair_sums = substring_indices_list
+                    .iter()
+                    .map(|indices| get_trace(&data[i], &data[j], indices, coeffs))
+                    .collect::<Vec<f64>>();
+                (i, pair_sums)
+            })
+        })
+        .inspect(|(i, _)| view.record_pair(*i))
+        .map(|(_, pair_sums)| pair_sums)
+        .reduce(
+            || vec![0.0; num_regions],
+            |mut acc, pair_sums| {
+                for (a, p) in acc.iter_mut().zip(pair_sums) {
+                    *a += p;
+                }
+                acc
+            },
+        );
+
+    view.finish();
     result
 }
 
+fn run_trace_calculation_batch(
+    data: Vec<Vec<u8>>,
+    subs_list: Vec<Vec<usize>>,
+    local_dim: u32,
+    num_threads: Option<usize>,
+    progress_mode: String,
+    progress_template: Option<String>,
+) -> PyResult<Vec<f64>> {
+    let substring_indices_list: Vec<Vec<usize>> = subs_list
+        .iter()
+        .map(|subs| subs.iter().map(|&i| i * 2).collect())
+        .collect();
+    let coeffs = QuditCoefficients::for_local_dim(local_dim)?;
+
+    if progress_mode == "multi" {
+        let num_chunks = num_threads.unwrap_or_else(rayon::current_num_threads);
+        return with_optional_pool(num_threads, || {
+            sum_pairs_batch_with_multi_progress(&data, &substring_indices_list, &coeffs, num_chunks)
+        });
+    }
+
+    with_optional_pool(num_threads, || {
+        sum_pairs_batch(
+            &data,
+            &substring_indices_list,
+            &coeffs,
+            &progress_mode,
+            progress_template.as_deref(),
+        )
+    })?
+}
+
+/// Thin wrapper over [`perform_trace_calculation_batch_u8`] that interns the string symbols
+/// to `u8` once up front.
+#[pyfunction]
+#[pyo3(signature = (data, subs_list, local_dim=2, num_threads=None, progress_mode="fancy".to_string(), progress_template=None))]
+fn perform_trace_calculation_batch(
+    data: Vec<Vec<String>>,
+    subs_list: Vec<Vec<usize>>,
+    local_dim: u32,
+    num_threads: Option<usize>,
+    progress_mode: String,
+    progress_template: Option<String>,
+) -> PyResult<Vec<f64>> {
+    run_trace_calculation_batch(
+        intern_symbols(&data)?,
+        subs_list,
+        local_dim,
+        num_threads,
+        progress_mode,
+        progress_template,
+    )
+}
+
+/// Same as [`perform_trace_calculation_batch`], but takes pre-encoded `u8` snapshots.
+#[pyfunction]
+#[pyo3(signature = (data, subs_list, local_dim=2, num_threads=None, progress_mode="fancy".to_string(), progress_template=None))]
+fn perform_trace_calculation_batch_u8(
+    data: Vec<Vec<u8>>,
+    subs_list: Vec<Vec<usize>>,
+    local_dim: u32,
+    num_threads: Option<usize>,
+    progress_mode: String,
+    progress_template: Option<String>,
+) -> PyResult<Vec<f64>> {
+    run_trace_calculation_batch(
+        data,
+        subs_list,
+        local_dim,
+        num_threads,
+        progress_mode,
+        progress_template,
+    )
+}
+
 /// A Python module implemented in Rust.
 #[pymodule]
 fn shadow_trace_rust(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(perform_trace_calculation, m)?)?;
+    m.add_function(wrap_pyfunction!(perform_trace_calculation_u8, m)?)?;
+    m.add_function(wrap_pyfunction!(perform_trace_calculation_with_error, m)?)?;
+    m.add_function(wrap_pyfunction!(perform_trace_calculation_with_error_u8, m)?)?;
+    m.add_function(wrap_pyfunction!(perform_trace_calculation_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(perform_trace_calculation_batch_u8, m)?)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn qudit_coefficients_reproduce_the_qubit_special_case() {
+        let coeffs = QuditCoefficients::for_local_dim(2).unwrap();
+        assert_eq!(coeffs.same_outcome, 5.0);
+        assert_eq!(coeffs.same_basis_diff_outcome, -4.0);
+        assert_eq!(coeffs.diff_basis, 0.5);
+    }
+
+    #[test]
+    fn qudit_coefficients_reject_dimension_below_two() {
+        assert!(QuditCoefficients::for_local_dim(1).is_err());
+        assert!(QuditCoefficients::for_local_dim(0).is_err());
+    }
+
+    #[test]
+    fn jackknife_matches_a_hand_computed_four_sample_example() {
+        // Pairwise values f(0,1)=1, f(0,2)=2, f(0,3)=3, f(1,2)=4, f(1,3)=5, f(2,3)=6, so
+        // R_k = sum_{j != k} f(k, j) is [6, 10, 12, 14] and T = sum(R_k) / 2 = 21.
+        let row_sums = [6.0, 10.0, 12.0, 14.0];
+        let (estimate, std_error) = jackknife_from_row_sums(&row_sums);
+
+        assert!((estimate - 3.5).abs() < 1e-9);
+        assert!((std_error - (105f64).sqrt() / 6.0).abs() < 1e-9);
+    }
+}